@@ -1,9 +1,9 @@
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
 use ctrlc;
-use openh264::{decoder::Decoder, nal_units};
-use rtsp_client::{Methods, Session};
-use std::io::Cursor;
-use tokio::net::UdpSocket;
+use openh264::decoder::Decoder;
+use rtsp_client::{Depacketizer, JitterBuffer, Methods, PcapWriter, ReceiverStats, SenderReport, Session};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -22,117 +22,128 @@ async fn main() -> Result<()> {
     println!("PLAY: \n{response}");
 
     if (&response).contains("200 OK") {
-        // Bind to my client UDP port which is provided in DESCRIBE method
-        // in the 'Transport' header
-        let udp_stream = UdpSocket::bind("0.0.0.0:4588").await?;
-
-        // Connect to the RTP camera server using IP and port
-        // provided in SETUP response
-        // In the RTP specs, the RTCP server should be
-        // port 6601 and will always need to be
-        // a different port
-        udp_stream.connect("192.168.86.138:6600").await?;
+        // The Transport header from SETUP tells us which ports the camera
+        // actually picked, so bind and connect to those instead of guessing.
+        let (udp_stream, rtcp_stream) = rtsp.bind_track_sockets(0, "0.0.0.0").await?;
+
+        // Record the live RTP stream to a pcap so it can be replayed later
+        // with `PcapReader` against the depacketizer/jitter buffer.
+        let mut pcap = match (udp_stream.local_addr(), udp_stream.peer_addr()) {
+            (Ok(local), Ok(peer)) => match (local.ip(), peer.ip()) {
+                (std::net::IpAddr::V4(src), std::net::IpAddr::V4(dst)) => {
+                    Some(PcapWriter::create("capture.pcap", src.octets(), local.port(), dst.octets(), peer.port())?)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let capture_start = Instant::now();
 
         // Set buffer to large enough to handle RTP packets
         // in my Wireshark analysis for this camera they
         // tended be a bit more than 1024
         let mut buf_rtp = [0u8; 2048];
+        let mut buf_rtcp = [0u8; 2048];
+
+        let mut depacketizer = Depacketizer::new();
+        let mut decoder = Decoder::new()?;
+        // Hold 16 packets (or 200ms, whichever comes first) so a handful of
+        // reordered slices can still land before we give up on them.
+        let mut jitter = JitterBuffer::new(16, Duration::from_millis(200));
+
+        let clock_rate = rtsp.media()[0].rtpmap.as_ref().map_or(90_000, |r| r.clock_rate);
+        let remote_ssrc = rtsp.transports()[0].ssrc.unwrap_or(0);
+        // Arbitrary local SSRC for our Receiver Reports; nothing here
+        // depends on it matching anything the camera already knows.
+        let mut stats = ReceiverStats::new(0x1234_5678, remote_ssrc, clock_rate);
+
+        // The server tears the session down if it doesn't hear from us
+        // within `timeout=`, so keep it alive at half that interval.
+        let keepalive_every = rtsp.session_timeout().unwrap_or(Duration::from_secs(60)) / 2;
+        let mut keepalive = tokio::time::interval(keepalive_every);
+        let mut receiver_report = tokio::time::interval(Duration::from_secs(5));
 
-        // Keep a separate buffer for the NAL units
-        // which should be the payload of each
-        // RTP packet. Some NAL units may not
-        // contain enough info on their own and
-        // may need more units, hence the buffer
-        let mut payload: Vec<u8> = Vec::new();
-
-        // Capture X num fragments and then exit
-        let mut sequence_started = false;
-
-        // Packet sequence for RTP using H264 and
-        // packetization-mode=1 (non-interleaved mode)
-        // Seems to go like this:
-        //
-        // Packet 1 - SPS (NAL Type 7) ---------------------|
-        // Packet 2 - PPS (NAL Type 8)                      |
-        // Packet 3 - SEI (NAL Type 6)                      |
-        // Packet 4 - FU-A (NAL Type 28) Start              |-- First Packet Sequence
-        // Packet 5 - FU-A (NAL Type 28)                    |
-        // Packet 6 - FU-A (NAL Type 28) End                |
-        // Packet 7 - Coded Slice Non-IDR (NAL Type 1)      |
-        // Packet 8+ - More Coded Slices (NAL Type 1)-------|
-        //
-        // Packet 1 - SPS (NAL Type 7)----------------------|
-        // Packet 2 - PPS (NAL Type 8)                      |
-        // Packet 3 - SEI (NAL Type 6)                      |
-        // Packet 4 - FU-A (NAL Type 28) Start              |-- Second Packet Sequence, etc.
-        // Packet 5 - FU-A (NAL Type 28)                    |
-        // Packet 6 - FU-A (NAL Type 28) End                |
-        // Packet 7 - Coded Slice Non-IDR (NAL Type 1)      |
-        // Packet 8+ - More Coded Slices (NAL Type 1)-------|
         loop {
-            let len = udp_stream.recv(&mut buf_rtp).await?;
-            let header_nal = &buf_rtp[12];
-
-            println!("{} bytes received", len);
-            println!("-----------\n{:08b}", header_nal);
-
-            // Check if this is an SPS packet
-            // First byte should be -> 01100111
-            if *header_nal == 103u8 {
-                if sequence_started {
-                    // This is the end of the previous sequence
-                    // Attempt to decode with H264
-                    let mut decoder = Decoder::new()?;
-                    match decoder.decode(payload.as_slice()) {
-                        Ok(maybe_yuv) => match maybe_yuv {
-                            Some(yuv) => println!("Decoded YUV!"),
-                            None => println!("Unable to decode to YUV"),
-                        },
-                        Err(e) => eprintln!("Decoding error: {e}"),
+            tokio::select! {
+                result = udp_stream.recv(&mut buf_rtp) => {
+                    let len = result?;
+                    let packet = &buf_rtp[..len];
+
+                    println!("{} bytes received", len);
+
+                    if let Some(pcap) = pcap.as_mut() {
+                        if let Err(e) = pcap.write_packet(capture_start.elapsed(), packet) {
+                            eprintln!("pcap capture error: {e}");
+                        }
+                    }
+
+                    // First 12 bytes AT LEAST are for the RTP header and this
+                    // header can be longer depending on the CC flag bit, but this
+                    // camera never sets it.
+                    let seq = u16::from_be_bytes([packet[2], packet[3]]);
+                    let marker = packet[1] & 0x80 != 0;
+                    let timestamp = u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]);
+                    let payload = packet[12..].to_vec();
+
+                    stats.record_packet(seq, timestamp);
+
+                    let mut released = jitter.push(seq, timestamp, marker, payload);
+                    released.extend(jitter.poll_timeout());
+
+                    for packet in released {
+                        if packet.after_gap {
+                            println!(
+                                "sequence gap detected ({} dropped so far), discarding in-progress access unit",
+                                jitter.dropped_frames()
+                            );
+                            depacketizer = Depacketizer::new();
+                            continue;
+                        }
+
+                        for frame in depacketizer.push(packet.timestamp, packet.marker, &packet.payload) {
+                            println!(
+                                "Access unit complete: {} NAL unit(s), random access = {}",
+                                frame.nal_units.len(),
+                                frame.is_random_access
+                            );
+
+                            // Stitch the access unit's NAL units into an Annex-B bitstream
+                            // for the decoder.
+                            let mut annex_b = Vec::new();
+                            for nal in &frame.nal_units {
+                                annex_b.extend_from_slice(&[0, 0, 0, 1]);
+                                annex_b.extend_from_slice(nal);
+                            }
+
+                            match decoder.decode(annex_b.as_slice()) {
+                                Ok(Some(_yuv)) => println!("Decoded YUV!"),
+                                Ok(None) => println!("Unable to decode to YUV"),
+                                Err(e) => eprintln!("Decoding error: {e}"),
+                            }
+                        }
+                    }
+                }
+
+                result = rtcp_stream.recv(&mut buf_rtcp) => {
+                    let len = result?;
+                    if let Some(sr) = SenderReport::parse(&buf_rtcp[..len]) {
+                        println!(
+                            "Sender Report: ntp={} rtp_timestamp={} packets={} octets={}",
+                            sr.ntp_timestamp, sr.rtp_timestamp, sr.packet_count, sr.octet_count
+                        );
+                        stats.record_sender_report(&sr);
                     }
+                }
 
-                    break;
-                } else {
-                    sequence_started = true;
+                _ = receiver_report.tick() => {
+                    let rr = stats.build_receiver_report();
+                    rtcp_stream.send(&rr).await?;
                 }
-            }
 
-            // If packetization-mode=1 found in SDP of Describe
-            // this is non-interleaved mode
-            // AND we find a packet of NAL type 28
-            // then server is sending FU-A type fragments
-            // AND each FU fragment has 2 byte headers
-            // So, NAL header 01111100 denotes FU-A fragment
-            if *header_nal == 124u8 {
-                // Get the 2nd byte for more header info
-                let header_fu = &buf_rtp[13];
-                println!("FU Header -----------\n{:08b}", header_fu);
-
-                // Add FU payload to buffer which is
-                // RTP packet minus RTP header minus FU header
-                // = packet - 12u8 - 2u8
-                // = packet - 14
-                payload.extend_from_slice(&buf_rtp[14..len]);
-                println!("FRAGMENT packet received. Buffer length: {}", payload.len());
-
-                // Look for an IDR fragment
-                // which is detemined by NAL type in last 5 bits
-                // IDR is NAL type 5 which is 101 for last 5 bits
-
-                // FU header = 10000101 -- fragment start
-                // FU header = 00000101 -- fragment middle
-                // FU header = 01000101 -- fragment end
-                if *header_fu == 133u8 || *header_fu == 69u8 || (*header_fu == 5u8) {
-                    // End of fragment, try to decode
-                    if *header_fu == 69u8 {}
+                _ = keepalive.tick() => {
+                    println!("sending keepalive");
+                    rtsp.send(Methods::GetParameter).await?;
                 }
-            } else {
-                // First 12 bytes AT LEAST are for the RTP
-                // header and this header can be longer
-                // depending on CC flag bit
-                // header.len() == 12 + (CC * 4)
-                payload.extend_from_slice(&buf_rtp[12..len]);
-                println!("Non fragment packet. Buffer length: {}", payload.len());
             }
         }
     }
@@ -0,0 +1,238 @@
+//! RFC 6184 depacketization: reassembles RTP H.264 payloads (packetization
+//! mode 1) into complete access units.
+
+/// NAL unit type 5: coded slice of an IDR picture.
+const NAL_TYPE_IDR: u8 = 5;
+/// NAL unit type 24: STAP-A, a single-time aggregation packet.
+const NAL_TYPE_STAP_A: u8 = 24;
+/// NAL unit type 28: FU-A, a fragmentation unit.
+const NAL_TYPE_FU_A: u8 = 28;
+
+/// One complete access unit: the NAL units that make up a single decodable
+/// frame, in the order they were received.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// RTP timestamp shared by every NAL unit in this access unit.
+    pub timestamp: u32,
+    /// Each entry is an owned NAL unit, header byte included.
+    pub nal_units: Vec<Vec<u8>>,
+    /// Set when the access unit contains an IDR (type 5) NAL unit.
+    pub is_random_access: bool,
+}
+
+/// Turns a stream of RTP payloads into [`Frame`]s.
+///
+/// Handles the three payload shapes packetization-mode-1 streams use: single
+/// NAL units (types 1-23), STAP-A aggregates (type 24), and FU-A fragments
+/// (type 28). An access unit ends when the RTP marker bit is set or when the
+/// RTP timestamp changes, whichever comes first.
+#[derive(Debug, Default)]
+pub struct Depacketizer {
+    current: Option<Frame>,
+    fu_buffer: Vec<u8>,
+    fu_in_progress: bool,
+}
+
+impl Depacketizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one RTP packet's payload (the RTP header already stripped off),
+    /// along with its timestamp and marker bit. Returns every [`Frame`] this
+    /// packet completes, in order: a packet that both ends the access unit
+    /// in progress (via a timestamp change) and, on its own, completes a new
+    /// one (via its marker bit) completes two.
+    pub fn push(&mut self, timestamp: u32, marker: bool, payload: &[u8]) -> Vec<Frame> {
+        if payload.is_empty() {
+            return Vec::new();
+        }
+
+        let mut completed = Vec::new();
+
+        if self.current.as_ref().is_some_and(|f| f.timestamp != timestamp) {
+            completed.extend(self.current.take());
+        }
+
+        self.add_payload(timestamp, payload);
+
+        if marker {
+            completed.extend(self.current.take());
+        }
+
+        completed
+    }
+
+    fn add_payload(&mut self, timestamp: u32, payload: &[u8]) {
+        match payload[0] & 0x1F {
+            NAL_TYPE_STAP_A => self.add_stap_a(timestamp, payload),
+            NAL_TYPE_FU_A => self.add_fu_a(timestamp, payload),
+            1..=23 => self.add_nal(timestamp, payload.to_vec()),
+            _ => {}
+        }
+    }
+
+    /// Walk a STAP-A's 16-bit length-prefixed NAL units and add each one.
+    fn add_stap_a(&mut self, timestamp: u32, payload: &[u8]) {
+        let mut offset = 1; // skip the STAP-A aggregator header byte itself
+        while offset + 2 <= payload.len() {
+            let size = u16::from_be_bytes([payload[offset], payload[offset + 1]]) as usize;
+            offset += 2;
+
+            if offset + size > payload.len() {
+                break;
+            }
+
+            self.add_nal(timestamp, payload[offset..offset + size].to_vec());
+            offset += size;
+        }
+    }
+
+    /// Reassemble an FU-A fragment, rebuilding the original NAL header from
+    /// the FU indicator's NRI bits and the FU header's type bits.
+    fn add_fu_a(&mut self, timestamp: u32, payload: &[u8]) {
+        if payload.len() < 2 {
+            return;
+        }
+
+        let fu_indicator = payload[0];
+        let fu_header = payload[1];
+        let start = fu_header & 0x80 != 0;
+        let end = fu_header & 0x40 != 0;
+
+        if start {
+            let nal_header = (fu_indicator & 0xE0) | (fu_header & 0x1F);
+            self.fu_buffer.clear();
+            self.fu_buffer.push(nal_header);
+            self.fu_in_progress = true;
+        }
+
+        if !self.fu_in_progress {
+            // Missed the start of this fragment; nothing sane to rebuild.
+            return;
+        }
+
+        self.fu_buffer.extend_from_slice(&payload[2..]);
+
+        if end {
+            let nal = std::mem::take(&mut self.fu_buffer);
+            self.fu_in_progress = false;
+            self.add_nal(timestamp, nal);
+        }
+    }
+
+    fn add_nal(&mut self, timestamp: u32, nal: Vec<u8>) {
+        if nal.is_empty() {
+            return;
+        }
+
+        let is_idr = nal[0] & 0x1F == NAL_TYPE_IDR;
+        let frame = self.current.get_or_insert_with(|| Frame {
+            timestamp,
+            nal_units: Vec::new(),
+            is_random_access: false,
+        });
+
+        frame.is_random_access |= is_idr;
+        frame.nal_units.push(nal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a single-NAL-unit payload with the given type and one byte of
+    /// made-up slice data, to keep test payloads short but distinguishable.
+    fn nal(nal_type: u8, data: u8) -> Vec<u8> {
+        vec![nal_type, data]
+    }
+
+    #[test]
+    fn single_nal_with_marker_completes_immediately() {
+        let mut d = Depacketizer::new();
+        let frames = d.push(100, true, &nal(1, 0xAA));
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].nal_units, vec![nal(1, 0xAA)]);
+        assert!(!frames[0].is_random_access);
+    }
+
+    #[test]
+    fn idr_nal_marks_random_access() {
+        let mut d = Depacketizer::new();
+        let frames = d.push(100, true, &nal(NAL_TYPE_IDR, 0x01));
+
+        assert!(frames[0].is_random_access);
+    }
+
+    #[test]
+    fn stap_a_aggregates_multiple_nal_units() {
+        let mut d = Depacketizer::new();
+        let mut payload = vec![NAL_TYPE_STAP_A];
+        for nal in [nal(7, 1), nal(8, 2), nal(5, 3)] {
+            payload.extend_from_slice(&(nal.len() as u16).to_be_bytes());
+            payload.extend_from_slice(&nal);
+        }
+
+        let frames = d.push(100, true, &payload);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].nal_units, vec![nal(7, 1), nal(8, 2), nal(5, 3)]);
+        assert!(frames[0].is_random_access);
+    }
+
+    #[test]
+    fn fu_a_reassembles_a_fragmented_nal() {
+        let mut d = Depacketizer::new();
+
+        // Original NAL: type 5 (IDR), NRI bits 0b10, payload [0xAA, 0xBB, 0xCC].
+        let fu_indicator = 0b0_10_11100; // FU-A (type 28) with NRI preserved
+        let start = [fu_indicator, 0b1000_0101, 0xAA]; // start bit set, original type 5
+        let middle = [fu_indicator, 0b0000_0101, 0xBB];
+        let end = [fu_indicator, 0b0100_0101, 0xCC];
+
+        assert!(d.push(100, false, &start).is_empty());
+        assert!(d.push(100, false, &middle).is_empty());
+        let frames = d.push(100, true, &end);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].nal_units, vec![vec![0b010_00101, 0xAA, 0xBB, 0xCC]]);
+        assert!(frames[0].is_random_access);
+    }
+
+    #[test]
+    fn timestamp_change_flushes_previous_access_unit() {
+        let mut d = Depacketizer::new();
+        d.push(100, false, &nal(1, 0xAA));
+
+        let frames = d.push(200, false, &nal(1, 0xBB));
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamp, 100);
+        assert_eq!(frames[0].nal_units, vec![nal(1, 0xAA)]);
+    }
+
+    /// Regression test: a packet that both flushes the in-progress access
+    /// unit (via a timestamp change) and, on its own, completes a brand new
+    /// one (via its marker bit) must not lose either frame.
+    #[test]
+    fn timestamp_change_and_marker_on_same_packet_yields_both_frames() {
+        let mut d = Depacketizer::new();
+        assert!(d.push(100, false, &nal(1, 0xAA)).is_empty());
+
+        let frames = d.push(200, true, &nal(1, 0xBB));
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].timestamp, 100);
+        assert_eq!(frames[0].nal_units, vec![nal(1, 0xAA)]);
+        assert_eq!(frames[1].timestamp, 200);
+        assert_eq!(frames[1].nal_units, vec![nal(1, 0xBB)]);
+    }
+
+    #[test]
+    fn empty_payload_is_ignored() {
+        let mut d = Depacketizer::new();
+        assert!(d.push(100, true, &[]).is_empty());
+    }
+}
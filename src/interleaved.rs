@@ -0,0 +1,112 @@
+//! RTSP interleaved ($-framing) transport: RTP/RTCP multiplexed over the
+//! same TCP connection as the textual RTSP requests, for cameras and
+//! firewalled setups that can't open a UDP session.
+
+use std::io::Error;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The `$` byte that introduces a binary interleaved frame.
+const FRAME_MARKER: u8 = 0x24;
+
+/// One item read off an interleaved RTSP connection: either a binary
+/// RTP/RTCP frame or a textual RTSP response, since servers are free to mix
+/// the two on the same socket (e.g. a keepalive reply arriving between RTP
+/// frames).
+#[derive(Debug)]
+pub enum InterleavedFrame {
+    /// A `$`-framed RTP (even channel) or RTCP (odd channel) payload.
+    Binary { channel: u8, payload: Vec<u8> },
+    /// A plain RTSP response.
+    Response(String),
+}
+
+impl InterleavedFrame {
+    pub fn is_rtp(&self) -> bool {
+        matches!(self, InterleavedFrame::Binary { channel, .. } if channel % 2 == 0)
+    }
+
+    pub fn is_rtcp(&self) -> bool {
+        matches!(self, InterleavedFrame::Binary { channel, .. } if channel % 2 == 1)
+    }
+}
+
+/// Read the next item off an interleaved connection: a `$`-framed
+/// RTP/RTCP packet, or (when the next byte isn't `$`) a textual RTSP
+/// response.
+pub async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S) -> Result<InterleavedFrame, Error> {
+    let mut marker = [0u8; 1];
+    stream.read_exact(&mut marker).await?;
+
+    if marker[0] != FRAME_MARKER {
+        // Not a binary frame: treat it as the start of a textual response.
+        let text = read_response(stream, vec![marker[0]]).await?;
+        return Ok(InterleavedFrame::Response(text));
+    }
+
+    let mut header = [0u8; 3];
+    stream.read_exact(&mut header).await?;
+    let channel = header[0];
+    let len = u16::from_be_bytes([header[1], header[2]]) as usize;
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+
+    Ok(InterleavedFrame::Binary { channel, payload })
+}
+
+/// Read a textual RTSP response from `stream`, accumulating reads until both
+/// the blank-line header terminator and any `Content-Length` body have
+/// fully arrived. A single `read` call only returns whatever landed in one
+/// TCP segment, so a response split across segments (a large `DESCRIBE`
+/// body, for instance) would otherwise come back truncated. `buffer` holds
+/// any bytes already read off the stream (e.g. the byte consumed to check
+/// for the `$` marker) that belong at the front of the response.
+pub(crate) async fn read_response<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    mut buffer: Vec<u8>,
+) -> Result<String, Error> {
+    let mut chunk = [0u8; 1024];
+
+    let headers_end = loop {
+        if let Some(pos) = header_terminator(&buffer) {
+            break pos;
+        }
+        let len = stream.read(&mut chunk).await?;
+        if len == 0 {
+            return Ok(String::from_utf8_lossy(&buffer).to_string());
+        }
+        buffer.extend_from_slice(&chunk[..len]);
+    };
+
+    let body_start = headers_end + 4;
+    let body_len = content_length(&buffer[..headers_end]);
+
+    while buffer.len() < body_start + body_len {
+        let len = stream.read(&mut chunk).await?;
+        if len == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..len]);
+    }
+
+    Ok(String::from_utf8_lossy(&buffer).to_string())
+}
+
+/// Find the offset of the `\r\n\r\n` that ends the header block, if it has
+/// arrived yet.
+fn header_terminator(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+/// Parse the `Content-Length` header out of a response's header block,
+/// defaulting to `0` (no body) when absent.
+fn content_length(headers: &[u8]) -> usize {
+    String::from_utf8_lossy(headers)
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case("Content-Length").then(|| value.trim().parse().ok()).flatten()
+        })
+        .unwrap_or(0)
+}
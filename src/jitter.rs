@@ -0,0 +1,220 @@
+//! Jitter/reorder buffer for UDP RTP packets.
+//!
+//! UDP delivers RTP packets out of order or drops them outright, but the
+//! [`crate::depacketizer::Depacketizer`] assumes slices of one access unit
+//! arrive in the order they were sent. This module sits between the socket
+//! and the depacketizer: it holds a short window of packets keyed by their
+//! 16-bit sequence number (wraparound-safe), and releases them in ascending
+//! order once the window fills or a packet has waited past a timeout.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// A sequence-number-ordered RTP packet, buffered but not yet released.
+#[derive(Debug)]
+struct Entry {
+    seq: u16,
+    timestamp: u32,
+    marker: bool,
+    payload: Vec<u8>,
+    received_at: Instant,
+}
+
+/// One packet released by [`JitterBuffer::push`] or
+/// [`JitterBuffer::poll_timeout`], in the order it should be fed to a
+/// [`crate::depacketizer::Depacketizer`].
+#[derive(Debug)]
+pub struct Released {
+    pub timestamp: u32,
+    pub marker: bool,
+    pub payload: Vec<u8>,
+    /// Set when a gap in sequence numbers was detected before this packet,
+    /// meaning an earlier slice of the access unit in progress was lost;
+    /// the caller should discard whatever the depacketizer has buffered
+    /// rather than splice across the loss.
+    pub after_gap: bool,
+}
+
+/// Reorders UDP RTP packets by sequence number before they reach the
+/// depacketizer, and flags gaps so incomplete access units can be dropped
+/// instead of corrupted.
+///
+/// Packets are released once more than `window` of them are buffered, or once the
+/// oldest buffered packet has waited longer than `timeout` — whichever
+/// comes first. A small window trades latency for reorder tolerance.
+#[derive(Debug)]
+pub struct JitterBuffer {
+    window: usize,
+    timeout: Duration,
+    packets: BTreeMap<i64, Entry>,
+    /// `(seq, extended)` of the most recently released packet, used to
+    /// linearize future sequence numbers across 16-bit wraparound.
+    reference: Option<(u16, i64)>,
+    /// Extended sequence number expected next; a release that doesn't match
+    /// it means a gap was detected.
+    next_expected: Option<i64>,
+    dropped_frames: u32,
+}
+
+impl JitterBuffer {
+    /// Create a buffer that holds at most `window` packets before forcing a
+    /// release, and never holds one longer than `timeout`.
+    pub fn new(window: usize, timeout: Duration) -> Self {
+        JitterBuffer {
+            window: window.max(1),
+            timeout,
+            packets: BTreeMap::new(),
+            reference: None,
+            next_expected: None,
+            dropped_frames: 0,
+        }
+    }
+
+    /// Number of times a release had to skip over a sequence number gap,
+    /// i.e. an access unit is missing a slice.
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped_frames
+    }
+
+    /// Buffer one RTP packet. Returns every packet now ready for release,
+    /// in ascending sequence order; empty until more than `window` packets
+    /// are buffered.
+    pub fn push(&mut self, seq: u16, timestamp: u32, marker: bool, payload: Vec<u8>) -> Vec<Released> {
+        if self.reference.is_none() {
+            self.reference = Some((seq, 0));
+        }
+
+        let extended = self.extend(seq);
+        self.packets.insert(
+            extended,
+            Entry {
+                seq,
+                timestamp,
+                marker,
+                payload,
+                received_at: Instant::now(),
+            },
+        );
+
+        let mut released = Vec::new();
+        while self.packets.len() > self.window {
+            released.extend(self.release_next());
+        }
+        released
+    }
+
+    /// Force out any packet that's been waiting longer than `timeout`, even
+    /// if the window hasn't filled. Callers should poll this periodically
+    /// (e.g. once per receive-loop iteration) so a gap that never gets
+    /// filled doesn't stall the stream indefinitely.
+    pub fn poll_timeout(&mut self) -> Vec<Released> {
+        let mut released = Vec::new();
+        while self
+            .packets
+            .values()
+            .next()
+            .is_some_and(|entry| entry.received_at.elapsed() >= self.timeout)
+        {
+            released.extend(self.release_next());
+        }
+        released
+    }
+
+    /// Map a 16-bit sequence number onto a monotonic `i64`, relative to the
+    /// most recently released packet, so ordering and arithmetic survive
+    /// wraparound from `0xFFFF` back to `0`.
+    fn extend(&self, seq: u16) -> i64 {
+        let Some((ref_seq, ref_extended)) = self.reference else {
+            return 0;
+        };
+        let diff = seq.wrapping_sub(ref_seq) as i16 as i64;
+        ref_extended + diff
+    }
+
+    fn release_next(&mut self) -> Option<Released> {
+        let &extended = self.packets.keys().next()?;
+        let entry = self.packets.remove(&extended).expect("key just read");
+
+        let after_gap = self.next_expected.is_some_and(|expected| expected != extended);
+        if after_gap {
+            self.dropped_frames += 1;
+        }
+
+        self.reference = Some((entry.seq, extended));
+        self.next_expected = Some(extended + 1);
+
+        Some(Released {
+            timestamp: entry.timestamp,
+            marker: entry.marker,
+            payload: entry.payload,
+            after_gap,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_in_ascending_sequence_order_despite_arrival_order() {
+        let mut jitter = JitterBuffer::new(3, Duration::from_secs(10));
+
+        // Arrive out of order: 2, 0, 1, 3 forces 0's release once the
+        // window (3) fills.
+        jitter.push(2, 200, false, vec![2]);
+        jitter.push(0, 0, false, vec![0]);
+        jitter.push(1, 100, false, vec![1]);
+        let released = jitter.push(3, 300, false, vec![3]);
+
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].payload, vec![0]);
+        assert!(!released[0].after_gap);
+    }
+
+    #[test]
+    fn sequence_wraparound_keeps_releasing_in_order() {
+        let mut jitter = JitterBuffer::new(2, Duration::from_secs(10));
+
+        jitter.push(0xFFFE, 100, false, vec![0xFE]);
+        jitter.push(0xFFFF, 200, false, vec![0xFF]);
+        let released = jitter.push(0, 300, false, vec![0]);
+
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].payload, vec![0xFE]);
+    }
+
+    #[test]
+    fn missing_packet_flags_the_next_release_as_after_gap() {
+        let mut jitter = JitterBuffer::new(1, Duration::from_secs(10));
+
+        // Seq 5 is skipped entirely.
+        let released = jitter.push(4, 100, false, vec![4]);
+        assert!(released.is_empty());
+
+        // Pushing 6 forces out the oldest buffered packet (4), which isn't
+        // itself after a gap.
+        let released = jitter.push(6, 300, false, vec![6]);
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].payload, vec![4]);
+        assert!(!released[0].after_gap);
+
+        // Forcing 6 out next is where the gap left by the missing 5 shows up.
+        let released = jitter.push(7, 400, false, vec![7]);
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].payload, vec![6]);
+        assert!(released[0].after_gap);
+        assert_eq!(jitter.dropped_frames(), 1);
+    }
+
+    #[test]
+    fn poll_timeout_forces_a_release_before_the_window_fills() {
+        let mut jitter = JitterBuffer::new(16, Duration::from_millis(0));
+
+        jitter.push(0, 100, false, vec![0]);
+        let released = jitter.poll_timeout();
+
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].payload, vec![0]);
+    }
+}
@@ -0,0 +1,20 @@
+//! A small async RTSP client for pulling H.264 video out of IP cameras.
+
+mod auth;
+pub mod depacketizer;
+pub mod interleaved;
+pub mod jitter;
+pub mod pcap;
+pub mod rtcp;
+pub mod sdp;
+mod session;
+pub mod transport;
+
+pub use depacketizer::{Depacketizer, Frame};
+pub use interleaved::InterleavedFrame;
+pub use jitter::{JitterBuffer, Released};
+pub use pcap::{PcapPacket, PcapReader, PcapWriter};
+pub use rtcp::{ReceiverStats, SenderReport};
+pub use sdp::{MediaDescription, RtpMap, SessionDescription};
+pub use session::{Methods, Session};
+pub use transport::Transport;
@@ -0,0 +1,272 @@
+//! Offline pcap capture/replay for the RTP pipeline.
+//!
+//! [`PcapReader`] replays a recorded `.pcap` file's RTP traffic so the
+//! depacketizer and jitter buffer can be exercised against captured camera
+//! traffic without a live camera. [`PcapWriter`] is the other direction:
+//! record a live UDP RTP stream to a `.pcap` while it's being played, for
+//! later replay. Implements just enough of the classic `libpcap` file
+//! format (Ethernet/IPv4/UDP only) to round-trip RTP, without pulling in an
+//! external dependency.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::time::Duration;
+
+/// Magic number for little-endian classic pcap files (as opposed to
+/// big-endian or the newer pcapng format).
+const MAGIC_NUMBER: u32 = 0xa1b2_c3d4;
+/// `LINKTYPE_ETHERNET`, the only link layer this module understands.
+const LINKTYPE_ETHERNET: u32 = 1;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const IP_PROTO_UDP: u8 = 17;
+
+/// One RTP packet replayed from a pcap file.
+#[derive(Debug, Clone)]
+pub struct PcapPacket {
+    /// Capture timestamp, relative to the first packet read from the file.
+    pub timestamp: Duration,
+    /// The UDP payload, i.e. the RTP packet with its Ethernet/IPv4/UDP
+    /// headers stripped.
+    pub rtp: Vec<u8>,
+}
+
+/// Reads RTP packets out of a pcap file recorded from a camera's UDP RTP
+/// stream, in capture order.
+pub struct PcapReader {
+    reader: BufReader<File>,
+    first_timestamp: Option<Duration>,
+}
+
+impl PcapReader {
+    /// Open `path` and validate its global header. Only little-endian
+    /// classic pcap over Ethernet is supported, which is what common
+    /// capture tools (tcpdump, Wireshark) write by default.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header)?;
+
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        if magic != MAGIC_NUMBER {
+            return Err(io::Error::other("not a little-endian pcap file"));
+        }
+
+        let network = u32::from_le_bytes(header[20..24].try_into().unwrap());
+        if network != LINKTYPE_ETHERNET {
+            return Err(io::Error::other("pcap file is not an Ethernet capture"));
+        }
+
+        Ok(PcapReader { reader, first_timestamp: None })
+    }
+
+    /// Read the next RTP packet, skipping any captured frame that isn't a
+    /// UDP/IPv4 datagram (e.g. ARP, or the RTSP TCP control connection if
+    /// it was captured on the same interface).
+    pub fn next_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+        loop {
+            let mut record_header = [0u8; 16];
+            match self.reader.read_exact(&mut record_header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+
+            let ts_sec = u32::from_le_bytes(record_header[0..4].try_into().unwrap());
+            let ts_usec = u32::from_le_bytes(record_header[4..8].try_into().unwrap());
+            let incl_len = u32::from_le_bytes(record_header[8..12].try_into().unwrap()) as usize;
+
+            let mut frame = vec![0u8; incl_len];
+            self.reader.read_exact(&mut frame)?;
+
+            let Some(rtp) = strip_headers(&frame) else {
+                continue;
+            };
+
+            let timestamp = Duration::new(ts_sec as u64, ts_usec * 1_000);
+            let first = *self.first_timestamp.get_or_insert(timestamp);
+
+            return Ok(Some(PcapPacket {
+                timestamp: timestamp.saturating_sub(first),
+                rtp,
+            }));
+        }
+    }
+
+    /// Replay every remaining packet to `sink`, sleeping between packets to
+    /// honor the original capture's inter-packet timing.
+    pub async fn replay_realtime<F: FnMut(Vec<u8>)>(&mut self, mut sink: F) -> io::Result<()> {
+        let mut previous = Duration::ZERO;
+        while let Some(packet) = self.next_packet()? {
+            if packet.timestamp > previous {
+                tokio::time::sleep(packet.timestamp - previous).await;
+            }
+            previous = packet.timestamp;
+            sink(packet.rtp);
+        }
+        Ok(())
+    }
+}
+
+/// Strip the Ethernet/IPv4/UDP headers off a captured frame, returning its
+/// UDP payload. Returns `None` for anything that isn't a UDP/IPv4 frame
+/// (transparently skipping one 802.1Q VLAN tag, if present).
+fn strip_headers(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut offset = 12; // dst MAC (6 bytes) + src MAC (6 bytes)
+
+    let mut ethertype = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+    offset += 2;
+
+    if ethertype == 0x8100 {
+        ethertype = u16::from_be_bytes(frame.get(offset + 2..offset + 4)?.try_into().ok()?);
+        offset += 4;
+    }
+
+    if ethertype != ETHERTYPE_IPV4 {
+        return None;
+    }
+
+    let ip = frame.get(offset..)?;
+    let ihl = (ip.first()? & 0x0F) as usize * 4;
+    if ip.get(9).copied()? != IP_PROTO_UDP {
+        return None;
+    }
+
+    let udp = ip.get(ihl..)?;
+    let udp_length = u16::from_be_bytes(udp.get(4..6)?.try_into().ok()?) as usize;
+
+    Some(udp.get(8..udp_length)?.to_vec())
+}
+
+/// Records a live UDP RTP stream to a pcap file while it's being played,
+/// for later replay with [`PcapReader`].
+pub struct PcapWriter {
+    writer: BufWriter<File>,
+    source: [u8; 4],
+    source_port: u16,
+    dest: [u8; 4],
+    dest_port: u16,
+}
+
+impl PcapWriter {
+    /// Create `path` and write the pcap global header. `source`/`dest` are
+    /// synthesized into each record's IPv4/UDP headers; the original
+    /// link-layer addressing isn't observable from a bound [`tokio::net::UdpSocket`],
+    /// so placeholder Ethernet MACs are used instead.
+    pub fn create(path: &str, source: [u8; 4], source_port: u16, dest: [u8; 4], dest_port: u16) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(&MAGIC_NUMBER.to_le_bytes())?;
+        writer.write_all(&2u16.to_le_bytes())?; // version_major
+        writer.write_all(&4u16.to_le_bytes())?; // version_minor
+        writer.write_all(&0i32.to_le_bytes())?; // thiszone
+        writer.write_all(&0u32.to_le_bytes())?; // sigfigs
+        writer.write_all(&65535u32.to_le_bytes())?; // snaplen
+        writer.write_all(&LINKTYPE_ETHERNET.to_le_bytes())?;
+
+        Ok(PcapWriter {
+            writer,
+            source,
+            source_port,
+            dest,
+            dest_port,
+        })
+    }
+
+    /// Append one RTP packet, synthesizing Ethernet/IPv4/UDP headers around
+    /// it and stamping it `elapsed` since capture start.
+    pub fn write_packet(&mut self, elapsed: Duration, rtp: &[u8]) -> io::Result<()> {
+        let frame = self.build_frame(rtp);
+
+        self.writer.write_all(&(elapsed.as_secs() as u32).to_le_bytes())?;
+        self.writer.write_all(&elapsed.subsec_micros().to_le_bytes())?;
+        self.writer.write_all(&(frame.len() as u32).to_le_bytes())?; // incl_len
+        self.writer.write_all(&(frame.len() as u32).to_le_bytes())?; // orig_len
+        self.writer.write_all(&frame)?;
+        self.writer.flush()
+    }
+
+    fn build_frame(&self, rtp: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(14 + 20 + 8 + rtp.len());
+
+        frame.extend_from_slice(&[0u8; 6]); // dst MAC, unobservable from a UDP socket
+        frame.extend_from_slice(&[0u8; 6]); // src MAC, same
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+
+        let udp_len = 8 + rtp.len();
+        let total_len = 20 + udp_len;
+
+        frame.push(0x45); // IPv4, IHL 5 (no options)
+        frame.push(0); // DSCP/ECN
+        frame.extend_from_slice(&(total_len as u16).to_be_bytes());
+        frame.extend_from_slice(&0u16.to_be_bytes()); // identification
+        frame.extend_from_slice(&0u16.to_be_bytes()); // flags/fragment offset
+        frame.push(64); // TTL
+        frame.push(IP_PROTO_UDP);
+        frame.extend_from_slice(&0u16.to_be_bytes()); // checksum, unused on replay
+        frame.extend_from_slice(&self.source);
+        frame.extend_from_slice(&self.dest);
+
+        frame.extend_from_slice(&self.source_port.to_be_bytes());
+        frame.extend_from_slice(&self.dest_port.to_be_bytes());
+        frame.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        frame.extend_from_slice(&0u16.to_be_bytes()); // checksum, unused on replay
+        frame.extend_from_slice(rtp);
+
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unique path under the system temp dir, cleaned up by [`TempPath::drop`].
+    struct TempPath(std::path::PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{name}-{:?}", std::thread::current().id()));
+            TempPath(path)
+        }
+
+        fn as_str(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn round_trips_rtp_packets_through_a_pcap_file() {
+        let path = TempPath::new("pcap-roundtrip-test");
+
+        let mut writer = PcapWriter::create(path.as_str(), [192, 168, 1, 10], 6000, [192, 168, 1, 1], 5000).unwrap();
+        writer.write_packet(Duration::from_millis(0), &[1, 2, 3]).unwrap();
+        writer.write_packet(Duration::from_millis(40), &[4, 5, 6, 7]).unwrap();
+
+        let mut reader = PcapReader::open(path.as_str()).unwrap();
+
+        let first = reader.next_packet().unwrap().unwrap();
+        assert_eq!(first.rtp, vec![1, 2, 3]);
+        assert_eq!(first.timestamp, Duration::from_millis(0));
+
+        let second = reader.next_packet().unwrap().unwrap();
+        assert_eq!(second.rtp, vec![4, 5, 6, 7]);
+        assert_eq!(second.timestamp, Duration::from_millis(40));
+
+        assert!(reader.next_packet().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_a_file_without_the_pcap_magic_number() {
+        let path = TempPath::new("pcap-bad-magic-test");
+        std::fs::write(path.as_str(), [0u8; 24]).unwrap();
+
+        assert!(PcapReader::open(path.as_str()).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! RTCP: parses incoming Sender Reports for A/V sync, and builds the
+//! Receiver Reports that keep a track's loss/jitter numbers visible to the
+//! server. See RFC 3550 §6.4.
+
+use std::time::Instant;
+
+/// The RTCP protocol version this client speaks.
+const RTCP_VERSION: u8 = 2;
+/// RTCP packet type 200: Sender Report.
+const PT_SENDER_REPORT: u8 = 200;
+/// RTCP packet type 201: Receiver Report.
+const PT_RECEIVER_REPORT: u8 = 201;
+
+/// The NTP/RTP timestamp pair from a Sender Report, letting a player line
+/// up this track's RTP clock against wall-clock time for A/V sync.
+#[derive(Debug, Clone, Copy)]
+pub struct SenderReport {
+    /// 64-bit NTP timestamp of when this report was sent.
+    pub ntp_timestamp: u64,
+    /// RTP timestamp corresponding to `ntp_timestamp`, in the track's clock.
+    pub rtp_timestamp: u32,
+    pub packet_count: u32,
+    pub octet_count: u32,
+}
+
+impl SenderReport {
+    /// Parse the Sender Report at the start of an RTCP packet (servers may
+    /// send it as the first packet of a compound RTCP packet; any packets
+    /// after it are ignored).
+    pub fn parse(packet: &[u8]) -> Option<Self> {
+        if packet.len() < 28 || packet[1] != PT_SENDER_REPORT {
+            return None;
+        }
+
+        Some(SenderReport {
+            ntp_timestamp: u64::from_be_bytes(packet[8..16].try_into().ok()?),
+            rtp_timestamp: u32::from_be_bytes(packet[16..20].try_into().ok()?),
+            packet_count: u32::from_be_bytes(packet[20..24].try_into().ok()?),
+            octet_count: u32::from_be_bytes(packet[24..28].try_into().ok()?),
+        })
+    }
+}
+
+/// Tracks what a Receiver Report needs for one track: cumulative packet
+/// loss, the highest sequence number received, and RFC 3550 §6.4.1
+/// interarrival jitter, plus the last Sender Report seen (for `LSR`/`DLSR`).
+#[derive(Debug)]
+pub struct ReceiverStats {
+    local_ssrc: u32,
+    remote_ssrc: u32,
+    clock_rate: u32,
+    packets_received: u64,
+    base_seq: Option<u16>,
+    /// Highest sequence number seen so far, as a signed diff from
+    /// `base_seq` so it keeps counting past a 16-bit wraparound.
+    highest_seq_diff: i64,
+    jitter: f64,
+    prev_transit: Option<i64>,
+    clock_origin: Option<(Instant, u32)>,
+    last_sr: Option<(u32, Instant)>,
+    /// Cumulative expected/lost as of the last [`Self::build_receiver_report`]
+    /// call, so `fraction_lost` can report loss since the last RR rather
+    /// than since the start of the stream.
+    reported_expected: u64,
+    reported_lost: u64,
+}
+
+impl ReceiverStats {
+    /// `local_ssrc` identifies us in the Receiver Report; `remote_ssrc` and
+    /// `clock_rate` come from the track's `SETUP` `Transport:` header and
+    /// `a=rtpmap:` clock rate respectively.
+    pub fn new(local_ssrc: u32, remote_ssrc: u32, clock_rate: u32) -> Self {
+        ReceiverStats {
+            local_ssrc,
+            remote_ssrc,
+            clock_rate: clock_rate.max(1),
+            packets_received: 0,
+            base_seq: None,
+            highest_seq_diff: 0,
+            jitter: 0.0,
+            prev_transit: None,
+            clock_origin: None,
+            last_sr: None,
+            reported_expected: 0,
+            reported_lost: 0,
+        }
+    }
+
+    /// Record one received RTP packet's sequence number and timestamp.
+    pub fn record_packet(&mut self, seq: u16, rtp_timestamp: u32) {
+        self.packets_received += 1;
+        let now = Instant::now();
+
+        let base = *self.base_seq.get_or_insert(seq);
+        let highest = base.wrapping_add(self.highest_seq_diff as u16);
+        let diff = self.highest_seq_diff + seq.wrapping_sub(highest) as i16 as i64;
+        self.highest_seq_diff = self.highest_seq_diff.max(diff);
+
+        let (origin_instant, origin_rtp) = *self.clock_origin.get_or_insert((now, rtp_timestamp));
+        let elapsed_rtp = now.duration_since(origin_instant).as_secs_f64() * self.clock_rate as f64;
+        let arrival = origin_rtp as i64 + elapsed_rtp as i64;
+        let transit = arrival - rtp_timestamp as i64;
+
+        if let Some(prev_transit) = self.prev_transit {
+            let delta = (transit - prev_transit).unsigned_abs() as f64;
+            self.jitter += (delta - self.jitter) / 16.0;
+        }
+        self.prev_transit = Some(transit);
+    }
+
+    /// Record a Sender Report's arrival, for this track's next Receiver
+    /// Report's `LSR`/`DLSR` fields.
+    pub fn record_sender_report(&mut self, report: &SenderReport) {
+        // LSR is the middle 32 bits of the 64-bit NTP timestamp.
+        let lsr = (report.ntp_timestamp >> 16) as u32;
+        self.last_sr = Some((lsr, Instant::now()));
+    }
+
+    /// Build one Receiver Report packet for this track.
+    pub fn build_receiver_report(&mut self) -> Vec<u8> {
+        let expected_cumulative = (self.highest_seq_diff + 1).max(0) as u64;
+        let lost_cumulative = expected_cumulative.saturating_sub(self.packets_received);
+
+        let expected_interval = expected_cumulative.saturating_sub(self.reported_expected);
+        let lost_interval = lost_cumulative.saturating_sub(self.reported_lost);
+        let fraction_lost = (lost_interval.min(expected_interval) * 256)
+            .checked_div(expected_interval)
+            .unwrap_or(0) as u8;
+        self.reported_expected = expected_cumulative;
+        self.reported_lost = lost_cumulative;
+
+        let highest_seq = self.base_seq.map_or(0, |base| base as i64 + self.highest_seq_diff) as u32;
+
+        let (lsr, dlsr) = match self.last_sr {
+            // DLSR units are 1/65536 sec, per RFC 3550 §6.4.1.
+            Some((lsr, received_at)) => (lsr, (received_at.elapsed().as_secs_f64() * 65536.0) as u32),
+            None => (0, 0),
+        };
+
+        let mut packet = Vec::with_capacity(32);
+        packet.push((RTCP_VERSION << 6) | 1); // RC = 1 report block
+        packet.push(PT_RECEIVER_REPORT);
+        packet.extend_from_slice(&7u16.to_be_bytes()); // length, in 32-bit words minus one
+        packet.extend_from_slice(&self.local_ssrc.to_be_bytes());
+        packet.extend_from_slice(&self.remote_ssrc.to_be_bytes());
+        packet.push(fraction_lost);
+        packet.extend_from_slice(&lost_cumulative.min(0xFF_FFFF).to_be_bytes()[5..8]);
+        packet.extend_from_slice(&highest_seq.to_be_bytes());
+        packet.extend_from_slice(&(self.jitter as u32).to_be_bytes());
+        packet.extend_from_slice(&lsr.to_be_bytes());
+        packet.extend_from_slice(&dlsr.to_be_bytes());
+
+        packet
+    }
+}
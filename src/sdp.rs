@@ -0,0 +1,202 @@
+//! A narrow SDP (RFC 4566) parser covering what an RTSP `DESCRIBE` response
+//! needs: the media list, per-track setup URL, dynamic payload type, and the
+//! codec parameters an H.264 decoder needs before the first RTP packet.
+
+/// One `m=` media section and the attributes relevant to RTSP setup.
+#[derive(Debug, Clone, Default)]
+pub struct MediaDescription {
+    /// Media type, e.g. `"video"` or `"audio"`.
+    pub media: String,
+    /// Dynamic payload type carried on the `m=` line (typically 96-127).
+    pub payload_type: u8,
+    /// `a=control:` attribute, used to build this track's `SETUP` URL. May
+    /// be relative to the session-level control URL.
+    pub control: Option<String>,
+    /// Encoding name and clock rate parsed from `a=rtpmap:`.
+    pub rtpmap: Option<RtpMap>,
+    /// Key/value pairs parsed from `a=fmtp:`.
+    pub fmtp: Vec<(String, String)>,
+    /// `a=framesize:` as `(width, height)`, when present.
+    pub framesize: Option<(u32, u32)>,
+}
+
+impl MediaDescription {
+    /// `packetization-mode` from `a=fmtp:`, defaulting to single NAL unit
+    /// mode (0) per RFC 6184 when absent.
+    pub fn packetization_mode(&self) -> u32 {
+        self.fmtp_value("packetization-mode")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Decoded `sprop-parameter-sets`: one entry per base64 unit, in order
+    /// (SPS first, then PPS), ready to hand to a decoder as raw NAL units.
+    pub fn sprop_parameter_sets(&self) -> Vec<Vec<u8>> {
+        let Some(raw) = self.fmtp_value("sprop-parameter-sets") else {
+            return Vec::new();
+        };
+
+        raw.split(',').filter_map(base64_decode).collect()
+    }
+
+    fn fmtp_value(&self, key: &str) -> Option<&str> {
+        self.fmtp
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Encoding name and clock rate from an `a=rtpmap:` attribute.
+#[derive(Debug, Clone)]
+pub struct RtpMap {
+    pub encoding_name: String,
+    pub clock_rate: u32,
+}
+
+/// A parsed SDP session description, as returned in an RTSP `DESCRIBE`
+/// response body.
+#[derive(Debug, Clone, Default)]
+pub struct SessionDescription {
+    /// Session-level `a=control:` attribute, if present.
+    pub control: Option<String>,
+    pub media: Vec<MediaDescription>,
+}
+
+impl SessionDescription {
+    /// Parse an SDP message body. Unrecognized or malformed lines are
+    /// skipped rather than treated as fatal, since servers vary widely in
+    /// which optional attributes they include.
+    pub fn parse(body: &str) -> Self {
+        let mut session = SessionDescription::default();
+        let mut current: Option<MediaDescription> = None;
+
+        for line in body.lines() {
+            let line = line.trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "m" => {
+                    if let Some(media) = current.take() {
+                        session.media.push(media);
+                    }
+                    current = parse_media_line(value);
+                }
+                "a" => {
+                    let Some((attr, attr_value)) = value.split_once(':') else {
+                        continue;
+                    };
+                    apply_attribute(current.as_mut(), &mut session, attr, attr_value);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(media) = current.take() {
+            session.media.push(media);
+        }
+
+        session
+    }
+}
+
+fn parse_media_line(value: &str) -> Option<MediaDescription> {
+    // `<media> <port> <proto> <fmt> ...`
+    let mut parts = value.split_whitespace();
+    let media = parts.next()?.to_string();
+    let _port = parts.next()?;
+    let _proto = parts.next()?;
+    let payload_type: u8 = parts.next()?.parse().ok()?;
+
+    Some(MediaDescription {
+        media,
+        payload_type,
+        ..Default::default()
+    })
+}
+
+fn apply_attribute(
+    media: Option<&mut MediaDescription>,
+    session: &mut SessionDescription,
+    attr: &str,
+    value: &str,
+) {
+    match (attr, media) {
+        ("control", Some(media)) => media.control = Some(value.to_string()),
+        ("control", None) => session.control = Some(value.to_string()),
+        ("rtpmap", Some(media)) => media.rtpmap = parse_rtpmap(value),
+        ("fmtp", Some(media)) => media.fmtp = parse_fmtp(value),
+        ("framesize", Some(media)) => media.framesize = parse_framesize(value),
+        _ => {}
+    }
+}
+
+/// `a=rtpmap:<payload type> <encoding name>/<clock rate>[/<channels>]`
+fn parse_rtpmap(value: &str) -> Option<RtpMap> {
+    let (_payload_type, rest) = value.split_once(' ')?;
+    let mut rest = rest.splitn(2, '/');
+    let encoding_name = rest.next()?.to_string();
+    let clock_rate: u32 = rest.next()?.split('/').next()?.parse().ok()?;
+
+    Some(RtpMap {
+        encoding_name,
+        clock_rate,
+    })
+}
+
+/// `a=fmtp:<payload type> <key>=<value>;<key>=<value>;...`
+fn parse_fmtp(value: &str) -> Vec<(String, String)> {
+    let Some((_payload_type, params)) = value.split_once(' ') else {
+        return Vec::new();
+    };
+
+    params
+        .split(';')
+        .filter_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// `a=framesize:<payload type> <width>-<height>`
+fn parse_framesize(value: &str) -> Option<(u32, u32)> {
+    let (_payload_type, dims) = value.split_once(' ')?;
+    let (width, height) = dims.split_once('-')?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+/// Minimal standard-alphabet base64 decoder so `sprop-parameter-sets` can be
+/// read without pulling in an external dependency.
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    fn value(byte: u8) -> Option<u8> {
+        match byte {
+            b'A'..=b'Z' => Some(byte - b'A'),
+            b'a'..=b'z' => Some(byte - b'a' + 26),
+            b'0'..=b'9' => Some(byte - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let input = input.trim().trim_end_matches('=');
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+
+    for byte in input.bytes() {
+        let v = value(byte)?;
+        buffer = (buffer << 6) | v as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
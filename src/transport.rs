@@ -0,0 +1,80 @@
+//! Parses the RTSP `Transport:` response header and binds the UDP sockets
+//! it describes, so callers no longer have to hard-code ports for one
+//! particular camera.
+
+use std::io::Error;
+
+use tokio::net::UdpSocket;
+
+/// A parsed `Transport:` header from a `SETUP` response.
+#[derive(Debug, Clone, Default)]
+pub struct Transport {
+    /// `client_port=A-B`: the client-side RTP/RTCP ports named in the response.
+    pub client_port: Option<(u16, u16)>,
+    /// `server_port=C-D`: the RTP/RTCP ports the server will send from.
+    pub server_port: Option<(u16, u16)>,
+    /// `ssrc=...`, the synchronization source the server will use.
+    pub ssrc: Option<u32>,
+    /// `mode=...`, e.g. `"PLAY"`.
+    pub mode: Option<String>,
+}
+
+impl Transport {
+    /// Parse a `Transport:` header value, e.g.
+    /// `RTP/AVP;unicast;client_port=4588-4589;server_port=6600-6601;ssrc=DEADBEEF;mode=PLAY`.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut transport = Transport::default();
+        let mut found_port_pair = false;
+
+        for field in header.split(';') {
+            let field = field.trim();
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "client_port" => {
+                    transport.client_port = parse_port_pair(value);
+                    found_port_pair |= transport.client_port.is_some();
+                }
+                "server_port" => {
+                    transport.server_port = parse_port_pair(value);
+                    found_port_pair |= transport.server_port.is_some();
+                }
+                "ssrc" => transport.ssrc = u32::from_str_radix(value, 16).ok(),
+                "mode" => transport.mode = Some(value.trim_matches('"').to_string()),
+                _ => {}
+            }
+        }
+
+        found_port_pair.then_some(transport)
+    }
+}
+
+fn parse_port_pair(value: &str) -> Option<(u16, u16)> {
+    let (a, b) = value.split_once('-')?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+/// Bind the RTP (even) and RTCP (odd) UDP sockets `transport.client_port`
+/// describes on `bind_ip`, and connect them to `server_host`'s advertised
+/// `server_port` so `recv` only yields packets from that camera.
+pub async fn bind_sockets(
+    transport: &Transport,
+    bind_ip: &str,
+    server_host: &str,
+) -> Result<(UdpSocket, UdpSocket), Error> {
+    let (rtp_port, rtcp_port) = transport
+        .client_port
+        .ok_or_else(|| Error::other("Transport header had no client_port"))?;
+
+    let rtp_socket = UdpSocket::bind((bind_ip, rtp_port)).await?;
+    let rtcp_socket = UdpSocket::bind((bind_ip, rtcp_port)).await?;
+
+    if let Some((server_rtp, server_rtcp)) = transport.server_port {
+        rtp_socket.connect((server_host, server_rtp)).await?;
+        rtcp_socket.connect((server_host, server_rtcp)).await?;
+    }
+
+    Ok((rtp_socket, rtcp_socket))
+}